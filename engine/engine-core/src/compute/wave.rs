@@ -5,6 +5,14 @@
 //! u_{t+dt} = 2 u_t - u_{t-dt} + (c*dt/dx)^2 * Laplacian(u_t)
 //!
 //! This struct stores three fields: prev (u_{t-dt}), curr (u_t), next (u_{t+dt}).
+//!
+//! Two optional features layer on top of the base scheme:
+//! - a per-cell velocity field, so `coeff` becomes `(c[i]*dt/dx)^2` for heterogeneous/layered
+//!   media instead of a single scalar `c` for the whole domain.
+//! - a damping "sponge" absorbing boundary, so waves reaching the edge of the domain are
+//!   dissipated instead of reflected back in by the default zero-Neumann edges.
+//! Both default to off (`velocity = None`, `damping` all zero), in which case `step` reduces
+//! bit-for-bit to the original scheme.
 
 #[derive(Debug, Clone)]
 pub struct Wavefield {
@@ -13,6 +21,11 @@ pub struct Wavefield {
     pub prev: Vec<f32>,
     pub curr: Vec<f32>,
     pub next: Vec<f32>,
+    /// Per-cell wave speed. `None` means every cell uses the scalar `c` passed to `step`.
+    velocity: Option<Vec<f32>>,
+    /// Per-cell absorbing-boundary damping coefficient. Zero everywhere until
+    /// `set_absorbing_border` is called.
+    damping: Vec<f32>,
 }
 
 impl Wavefield {
@@ -25,6 +38,8 @@ impl Wavefield {
             prev: vec![0.0; len],
             curr: vec![0.0; len],
             next: vec![0.0; len],
+            velocity: None,
+            damping: vec![0.0; len],
         }
     }
 
@@ -35,9 +50,15 @@ impl Wavefield {
 
     /// Reset all fields to zero.
     pub fn reset(&mut self) {
-        for v in &mut self.prev { *v = 0.0; }
-        for v in &mut self.curr { *v = 0.0; }
-        for v in &mut self.next { *v = 0.0; }
+        for v in &mut self.prev {
+            *v = 0.0;
+        }
+        for v in &mut self.curr {
+            *v = 0.0;
+        }
+        for v in &mut self.next {
+            *v = 0.0;
+        }
     }
 
     /// Seed a point impulse at (x, y) into `curr` (optionally with value).
@@ -49,28 +70,144 @@ impl Wavefield {
         }
     }
 
+    /// Serialize `width`, `height`, and the `prev`/`curr`/`next` buffers into a flat byte
+    /// buffer, so a running simulation can be checkpointed (e.g. into the kernel's
+    /// content-addressed state store) and later restored with `from_bytes`. The velocity
+    /// field and absorbing-boundary configuration are not part of the snapshot; re-apply
+    /// them after restoring if needed.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(16 + self.len() * 3 * 4);
+        buf.extend_from_slice(&(self.width as u64).to_le_bytes());
+        buf.extend_from_slice(&(self.height as u64).to_le_bytes());
+        for field in [&self.prev, &self.curr, &self.next] {
+            for v in field {
+                buf.extend_from_slice(&v.to_le_bytes());
+            }
+        }
+        buf
+    }
+
+    /// Deserialize a buffer produced by `to_bytes` back into a `Wavefield`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, WavefieldDecodeError> {
+        if bytes.len() < 16 {
+            return Err(WavefieldDecodeError::new(
+                "buffer too short for width/height header",
+            ));
+        }
+        let width = u64::from_le_bytes(bytes[0..8].try_into().unwrap()) as usize;
+        let height = u64::from_le_bytes(bytes[8..16].try_into().unwrap()) as usize;
+        let len = width
+            .checked_mul(height)
+            .ok_or_else(|| WavefieldDecodeError::new("width*height overflows"))?;
+        // Every arithmetic step from here on operates on attacker/corruption-controlled
+        // `width`/`height`, so it must be checked all the way through rather than just at the
+        // `width.checked_mul(height)` step: a `len` that itself fits in a `usize` can still
+        // overflow once multiplied by the 3 buffers * 4 bytes/f32 below.
+        let field_len = len
+            .checked_mul(4)
+            .ok_or_else(|| WavefieldDecodeError::new("field byte length overflows"))?;
+        let expected_len = field_len
+            .checked_mul(3)
+            .and_then(|n| n.checked_add(16))
+            .ok_or_else(|| WavefieldDecodeError::new("snapshot byte length overflows"))?;
+        if bytes.len() != expected_len {
+            return Err(WavefieldDecodeError::new(format!(
+                "expected {} bytes for {}x{} snapshot, got {}",
+                expected_len,
+                width,
+                height,
+                bytes.len()
+            )));
+        }
+
+        let mut offset = 16;
+        let mut read_field = || -> Vec<f32> {
+            let field = bytes[offset..offset + field_len]
+                .chunks_exact(4)
+                .map(|c| f32::from_le_bytes(c.try_into().unwrap()))
+                .collect();
+            offset += field_len;
+            field
+        };
+        let prev = read_field();
+        let curr = read_field();
+        let next = read_field();
+
+        Ok(Self {
+            width,
+            height,
+            prev,
+            curr,
+            next,
+            velocity: None,
+            damping: vec![0.0; len],
+        })
+    }
+
+    /// Install a per-cell wave-speed field, overriding the scalar `c` passed to `step` for
+    /// every cell. Enables heterogeneous/layered media. `velocity.len()` must equal
+    /// `self.len()`.
+    pub fn set_velocity_field(&mut self, velocity: Vec<f32>) {
+        assert_eq!(
+            velocity.len(),
+            self.len(),
+            "velocity field length must match width*height"
+        );
+        self.velocity = Some(velocity);
+    }
+
+    /// Install a damping "sponge" absorbing boundary: a band `width` cells deep along every
+    /// edge where the damping coefficient ramps quadratically from 0 in the interior up to
+    /// `d_max` at the edge, so outgoing waves are dissipated instead of reflected by the
+    /// default zero-Neumann edges. Pass `width == 0` to disable (zero damping everywhere).
+    pub fn set_absorbing_border(&mut self, width: usize, d_max: f32) {
+        let nx = self.width;
+        let ny = self.height;
+        for y in 0..ny {
+            for x in 0..nx {
+                let depth = [x, nx.saturating_sub(1 + x), y, ny.saturating_sub(1 + y)]
+                    .into_iter()
+                    .min()
+                    .unwrap_or(0);
+                let i = y * nx + x;
+                self.damping[i] = if width > 0 && depth < width {
+                    let t = (width - depth) as f32 / width as f32;
+                    d_max * t * t
+                } else {
+                    0.0
+                };
+            }
+        }
+    }
+
     /// Step the simulation forward by one timestep.
     ///
-    /// - `c`: wave speed (units consistent with dt/dx)
+    /// - `c`: wave speed (units consistent with dt/dx); overridden per-cell if a velocity
+    ///   field was installed via `set_velocity_field`
     /// - `dt`: timestep
     /// - `dx`: spatial grid spacing (same units as dt*c)
     ///
     /// Boundary conditions: simple zero-Neumann (copy neighbor) implemented by ignoring
-    /// the Laplacian at boundaries (i.e., we don't read outside array; edges are left computed with available neighbors).
+    /// the Laplacian at boundaries (i.e., we don't read outside array; edges are left computed
+    /// with available neighbors), optionally overlaid with an absorbing sponge layer installed
+    /// via `set_absorbing_border`.
     pub fn step(&mut self, c: f32, dt: f32, dx: f32) {
         let nx = self.width;
         let ny = self.height;
         let len = self.len();
-        if len == 0 { return; }
-
-        // stability factor
-        let coeff = (c * dt / dx).powi(2);
+        if len == 0 {
+            return;
+        }
 
         // index helper
         let idx = |x: isize, y: isize| -> Option<usize> {
-            if x < 0 || y < 0 { return None; }
+            if x < 0 || y < 0 {
+                return None;
+            }
             let (xu, yu) = (x as usize, y as usize);
-            if xu >= nx || yu >= ny { return None; }
+            if xu >= nx || yu >= ny {
+                return None;
+            }
             Some(yu * nx + xu)
         };
 
@@ -80,15 +217,24 @@ impl Wavefield {
                 let center_i = idx(x, y).unwrap();
                 // fetch neighbor values (if out-of-bounds, use center value => zero Neumann-ish)
                 let center = self.curr[center_i];
-                let left   = idx(x - 1, y).map(|i| self.curr[i]).unwrap_or(center);
-                let right  = idx(x + 1, y).map(|i| self.curr[i]).unwrap_or(center);
-                let up     = idx(x, y - 1).map(|i| self.curr[i]).unwrap_or(center);
-                let down   = idx(x, y + 1).map(|i| self.curr[i]).unwrap_or(center);
+                let left = idx(x - 1, y).map(|i| self.curr[i]).unwrap_or(center);
+                let right = idx(x + 1, y).map(|i| self.curr[i]).unwrap_or(center);
+                let up = idx(x, y - 1).map(|i| self.curr[i]).unwrap_or(center);
+                let down = idx(x, y + 1).map(|i| self.curr[i]).unwrap_or(center);
 
                 let lap = left + right + up + down - 4.0 * center;
 
-                // wave update: next = 2*curr - prev + coeff * lap
-                self.next[center_i] = 2.0 * center - self.prev[center_i] + coeff * lap;
+                // per-cell wave speed (falls back to the scalar `c` with no velocity field)
+                let local_c = self.velocity.as_ref().map(|v| v[center_i]).unwrap_or(c);
+                let coeff = (local_c * dt / dx).powi(2);
+
+                // damped wave update: next = (2*curr - (1 - d*dt)*prev + coeff*lap) / (1 + d*dt)
+                // reduces bit-for-bit to the undamped scheme when damping is zero, since
+                // multiplying/dividing by exactly 1.0 doesn't perturb the result.
+                let damp_term = self.damping[center_i] * dt;
+                self.next[center_i] = (2.0 * center - (1.0 - damp_term) * self.prev[center_i]
+                    + coeff * lap)
+                    / (1.0 + damp_term);
             }
         }
 
@@ -97,6 +243,119 @@ impl Wavefield {
         std::mem::swap(&mut self.curr, &mut self.next);
 
         // optional: clear next (not strictly necessary but keeps expectations)
-        for v in &mut self.next { *v = 0.0; }
+        for v in &mut self.next {
+            *v = 0.0;
+        }
+    }
+}
+
+/// Error returned by `Wavefield::from_bytes` when the snapshot buffer is truncated or
+/// otherwise malformed.
+#[derive(Debug, Clone)]
+pub struct WavefieldDecodeError {
+    message: String,
+}
+
+impl WavefieldDecodeError {
+    fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for WavefieldDecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid wavefield snapshot: {}", self.message)
+    }
+}
+
+impl std::error::Error for WavefieldDecodeError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// With no velocity field and no absorbing border installed, `damping` is zero for every
+    /// cell, so `step`'s damped recurrence must reduce bit-for-bit to the original undamped
+    /// scheme `next = 2*curr - prev + coeff*lap` (multiplying/dividing by exactly 1.0 is
+    /// lossless in IEEE754). Compute that reference scheme independently here rather than via
+    /// `Wavefield::step`, so a regression that reintroduces a stray `damp_term` can't also
+    /// slip into the expected values.
+    #[test]
+    fn step_with_zero_damping_matches_undamped_scheme_bit_for_bit() {
+        let (c, dt, dx) = (1.0_f32, 0.1_f32, 1.0_f32);
+        let mut field = Wavefield::new(5, 5);
+        field.seed_point(2, 2, 1.0);
+
+        for _ in 0..4 {
+            let nx = field.width;
+            let ny = field.height;
+            let coeff = (c * dt / dx).powi(2);
+            let idx = |x: isize, y: isize| -> Option<usize> {
+                if x < 0 || y < 0 || x as usize >= nx || y as usize >= ny {
+                    return None;
+                }
+                Some(y as usize * nx + x as usize)
+            };
+            let mut expected_next = vec![0.0_f32; field.len()];
+            for y in 0..ny as isize {
+                for x in 0..nx as isize {
+                    let center_i = idx(x, y).unwrap();
+                    let center = field.curr[center_i];
+                    let left = idx(x - 1, y).map(|i| field.curr[i]).unwrap_or(center);
+                    let right = idx(x + 1, y).map(|i| field.curr[i]).unwrap_or(center);
+                    let up = idx(x, y - 1).map(|i| field.curr[i]).unwrap_or(center);
+                    let down = idx(x, y + 1).map(|i| field.curr[i]).unwrap_or(center);
+                    let lap = left + right + up + down - 4.0 * center;
+                    expected_next[center_i] = 2.0 * center - field.prev[center_i] + coeff * lap;
+                }
+            }
+            let expected_curr = field.curr.clone();
+
+            field.step(c, dt, dx);
+
+            assert_eq!(field.prev, expected_curr);
+            assert_eq!(field.curr, expected_next);
+        }
+    }
+
+    #[test]
+    fn to_bytes_from_bytes_round_trips() {
+        let mut field = Wavefield::new(4, 3);
+        field.seed_point(1, 2, 0.5);
+        field.step(1.0, 0.1, 1.0);
+        field.step(1.0, 0.1, 1.0);
+
+        let bytes = field.to_bytes();
+        let restored = Wavefield::from_bytes(&bytes).expect("round-trip should decode");
+
+        assert_eq!(restored.width, field.width);
+        assert_eq!(restored.height, field.height);
+        assert_eq!(restored.prev, field.prev);
+        assert_eq!(restored.curr, field.curr);
+        assert_eq!(restored.next, field.next);
+    }
+
+    #[test]
+    fn from_bytes_rejects_truncated_buffer_instead_of_panicking() {
+        let field = Wavefield::new(4, 3);
+        let mut bytes = field.to_bytes();
+        bytes.truncate(bytes.len() - 1);
+
+        assert!(Wavefield::from_bytes(&bytes).is_err());
+    }
+
+    #[test]
+    fn from_bytes_rejects_overflowing_dimensions_instead_of_panicking() {
+        // `width * height` fits in a `usize` (passes the first `checked_mul`), but multiplying
+        // that by 3 buffers * 4 bytes/f32 overflows — this must return `Err`, not panic.
+        let width: u64 = 1_240_000_000;
+        let height: u64 = 1_240_000_000;
+        let mut bytes = Vec::with_capacity(16);
+        bytes.extend_from_slice(&width.to_le_bytes());
+        bytes.extend_from_slice(&height.to_le_bytes());
+
+        assert!(Wavefield::from_bytes(&bytes).is_err());
     }
 }
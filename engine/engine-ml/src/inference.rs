@@ -1,9 +1,14 @@
 //! inference.rs
 //!
 //! Generic inference traits + a simple runtime wrapper.
-//! Backends can implement `ModelBackend` and plug into the engine.
+//! Backends can implement `ModelBackend` (blocking) or `AsyncModelBackend` (async) and plug
+//! into the engine. Native, blocking backends (ONNX runtime, GGML, Candle) implement
+//! `ModelBackend`; backends that are inherently asynchronous (WASM/WebGPU inference awaiting
+//! a JS promise) implement `AsyncModelBackend` instead. Both kinds plug into the same
+//! `InferenceRuntime` wrapper.
 
 use anyhow::Result;
+use async_trait::async_trait;
 
 /// A generic machine-learning inference backend.
 ///
@@ -22,6 +27,30 @@ pub trait ModelBackend: Send + Sync {
     fn infer(&self, input: &InferenceInput) -> Result<InferenceOutput>;
 }
 
+/// Async counterpart to `ModelBackend`, for backends that can only be driven asynchronously
+/// (e.g. a WASM/WebGPU backend awaiting a JS promise).
+#[async_trait]
+pub trait AsyncModelBackend: Send + Sync {
+    /// Load a model from bytes
+    async fn load(bytes: &[u8]) -> Result<Self>
+    where
+        Self: Sized;
+
+    /// Run inference on the given input tensor(s)
+    async fn infer(&self, input: &InferenceInput) -> Result<InferenceOutput>;
+
+    /// Run inference over a batch of inputs, amortizing model-call overhead across many
+    /// `InferenceInput`s in one round trip. The default implementation awaits `infer`
+    /// sequentially; backends that can batch natively should override this.
+    async fn infer_batch(&self, inputs: &[InferenceInput]) -> Result<Vec<InferenceOutput>> {
+        let mut outputs = Vec::with_capacity(inputs.len());
+        for input in inputs {
+            outputs.push(self.infer(input).await?);
+        }
+        Ok(outputs)
+    }
+}
+
 /// Simple structured input
 #[derive(Debug, Clone)]
 pub struct InferenceInput {
@@ -49,8 +78,9 @@ impl InferenceOutput {
 }
 
 /// High-level runtime wrapper used by other engine crates.
-/// This allows you to swap backends at compile-time or run-time.
-pub struct InferenceRuntime<B: ModelBackend> {
+/// This allows you to swap backends at compile-time or run-time. The same wrapper hosts both
+/// blocking `ModelBackend`s (native callers) and async `AsyncModelBackend`s (WASM/WebGPU).
+pub struct InferenceRuntime<B> {
     backend: B,
 }
 
@@ -65,3 +95,152 @@ impl<B: ModelBackend> InferenceRuntime<B> {
     }
 }
 
+impl<B: AsyncModelBackend> InferenceRuntime<B> {
+    pub async fn from_bytes_async(bytes: &[u8]) -> Result<Self> {
+        let backend = B::load(bytes).await?;
+        Ok(Self { backend })
+    }
+
+    /// Run inference, retrying transient backend errors for at most `max_attempts` attempts in
+    /// all (at least one), with no delay between attempts — callers needing backoff should wrap
+    /// this in their own sleep.
+    pub async fn infer_async(
+        &self,
+        input: &InferenceInput,
+        max_attempts: u32,
+    ) -> Result<InferenceOutput> {
+        let mut last_err = None;
+        for _ in 0..max_attempts.max(1) {
+            match self.backend.infer(input).await {
+                Ok(output) => return Ok(output),
+                Err(e) => last_err = Some(e),
+            }
+        }
+        Err(last_err.expect("max_attempts.max(1) guarantees at least one attempt"))
+    }
+
+    /// Batched counterpart to `infer_async`: run `inputs` through `AsyncModelBackend::infer_batch`,
+    /// retrying the whole batch on transient backend errors for at most `max_attempts` attempts
+    /// in all.
+    pub async fn infer_batch_async(
+        &self,
+        inputs: &[InferenceInput],
+        max_attempts: u32,
+    ) -> Result<Vec<InferenceOutput>> {
+        let mut last_err = None;
+        for _ in 0..max_attempts.max(1) {
+            match self.backend.infer_batch(inputs).await {
+                Ok(outputs) => return Ok(outputs),
+                Err(e) => last_err = Some(e),
+            }
+        }
+        Err(last_err.expect("max_attempts.max(1) guarantees at least one attempt"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    /// Drive a future to completion without pulling in an async runtime dependency: every
+    /// future in these tests resolves on its first poll (no real I/O), so a no-op waker is
+    /// all that's needed.
+    fn block_on<F: std::future::Future>(future: F) -> F::Output {
+        use std::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+
+        fn noop(_: *const ()) {}
+        fn clone(_: *const ()) -> RawWaker {
+            RawWaker::new(std::ptr::null(), &VTABLE)
+        }
+        static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+
+        let raw_waker = RawWaker::new(std::ptr::null(), &VTABLE);
+        let waker = unsafe { Waker::from_raw(raw_waker) };
+        let mut cx = Context::from_waker(&waker);
+        let mut future = Box::pin(future);
+        loop {
+            if let Poll::Ready(v) = future.as_mut().poll(&mut cx) {
+                return v;
+            }
+        }
+    }
+
+    /// Fails `load`'s first byte worth of calls to `infer`, then succeeds by echoing the input
+    /// back as output.
+    struct FlakyBackend {
+        remaining_failures: AtomicU32,
+    }
+
+    #[async_trait]
+    impl AsyncModelBackend for FlakyBackend {
+        async fn load(bytes: &[u8]) -> Result<Self> {
+            let initial_failures = bytes.first().copied().unwrap_or(0) as u32;
+            Ok(Self {
+                remaining_failures: AtomicU32::new(initial_failures),
+            })
+        }
+
+        async fn infer(&self, input: &InferenceInput) -> Result<InferenceOutput> {
+            let remaining = self.remaining_failures.load(Ordering::SeqCst);
+            if remaining > 0 {
+                self.remaining_failures
+                    .store(remaining - 1, Ordering::SeqCst);
+                anyhow::bail!("transient backend failure, {} left to fail", remaining);
+            }
+            Ok(InferenceOutput::new(input.data.clone(), input.dims.clone()))
+        }
+    }
+
+    #[test]
+    fn infer_async_retries_until_the_backend_succeeds() {
+        block_on(async {
+            // Two failures configured, three attempts allowed: the third attempt must succeed.
+            let runtime = InferenceRuntime::<FlakyBackend>::from_bytes_async(&[2])
+                .await
+                .expect("load flaky backend");
+            let input = InferenceInput::new(vec![1.0, 2.0], vec![2]);
+
+            let output = runtime
+                .infer_async(&input, 3)
+                .await
+                .expect("should succeed on the 3rd attempt");
+            assert_eq!(output.data, input.data);
+        });
+    }
+
+    #[test]
+    fn infer_async_surfaces_the_last_error_once_attempts_are_exhausted() {
+        block_on(async {
+            // Five failures configured, only two attempts allowed: both fail, so the caller
+            // should see the second attempt's error.
+            let runtime = InferenceRuntime::<FlakyBackend>::from_bytes_async(&[5])
+                .await
+                .expect("load flaky backend");
+            let input = InferenceInput::new(vec![1.0], vec![1]);
+
+            let err = runtime
+                .infer_async(&input, 2)
+                .await
+                .expect_err("should give up after 2 attempts");
+            assert!(err.to_string().contains("4 left to fail"));
+        });
+    }
+
+    #[test]
+    fn infer_batch_async_honors_max_attempts() {
+        block_on(async {
+            let runtime = InferenceRuntime::<FlakyBackend>::from_bytes_async(&[1])
+                .await
+                .expect("load flaky backend");
+            let inputs = vec![InferenceInput::new(vec![1.0], vec![1])];
+
+            let outputs = runtime
+                .infer_batch_async(&inputs, 2)
+                .await
+                .expect("should succeed on the 2nd attempt");
+            assert_eq!(outputs.len(), 1);
+            assert_eq!(outputs[0].data, inputs[0].data);
+        });
+    }
+}
@@ -1,14 +1,16 @@
 use anyhow::{bail, Context, Result};
 use chrono::Utc;
+use engine_core::compute::wave::Wavefield;
 use petgraph::graph::{DiGraph, NodeIndex};
-use petgraph::visit::Topo;
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::Mutex;
 use uuid::Uuid;
 
 /// Simple filesystem layout (under a root dir)
@@ -20,13 +22,27 @@ const STORAGE_ROOT: &str = "kernel_store";
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct TransformSpec {
     pub id: String,
-    /// Exec command to run the transform. It is invoked with two positional args:
-    /// <input_path> <output_path>
-    /// Example: "python3 scripts/add.py" or "./bin/my_transform"
-    pub exec_command: String,
+    /// How this transform is actually run.
+    pub kind: TransformKind,
     pub meta: serde_json::Value,
 }
 
+/// The execution strategy for a transform. Both variants share the same `<input_path>
+/// <output_path>` argv contract, so `run_transform_with_io` can treat them uniformly up to
+/// the point of invocation.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TransformKind {
+    /// Spawn an external process via `std::process::Command`. It is invoked with two
+    /// positional args: <input_path> <output_path>.
+    /// Example: "python3 scripts/add.py" or "./bin/my_transform"
+    Native { exec_command: String },
+    /// Run a compiled WASI module in-process via wasmtime, sandboxed and without forking.
+    /// The module's `_start` entrypoint is invoked with the input/output files mounted
+    /// through a WASI preopen, keeping the same `<input_path> <output_path>` argv contract.
+    Wasm { module_path: String },
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TracePacket {
     pub trace_id: String,
@@ -36,10 +52,255 @@ pub struct TracePacket {
     pub inputs_hash: String,
     pub outputs_hash: String,
     pub duration_ms: u128,
+    /// Number of attempts made (1 if the transform succeeded or failed outright with no
+    /// retries). See `RetryPolicy`.
+    pub attempt_count: u32,
     pub resource_usage: serde_json::Value,
     pub error: Option<String>,
 }
 
+/// Controls how many times a failing transform is retried and how long to wait between
+/// attempts. Backoff grows as `initial_backoff_ms * multiplier^attempt`. The default performs
+/// no retries, preserving the historical "fail fast" behavior.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub initial_backoff_ms: u64,
+    pub multiplier: f64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 1,
+            initial_backoff_ms: 0,
+            multiplier: 1.0,
+        }
+    }
+}
+
+/// A declarative type contract for one named field of a transform's input or output JSON.
+/// Lets workflow authors catch a type mismatch (e.g. a node expecting an integer silently
+/// receiving a string) at the kernel boundary instead of deep inside a subprocess.
+///
+/// Parsed from short names via `FromStr`: `"bytes"`, `"int"`, `"float"`, `"bool"`,
+/// `"timestamp"`, or `"timestamp|<chrono format>"` for a custom timestamp format. Serializes
+/// to/from that same string form in JSON, so a `GraphNode`'s schema reads naturally as
+/// `{"count": "int", "seen_at": "timestamp|%Y-%m-%d"}`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Conversion {
+    Bytes,
+    Integer,
+    Float,
+    Boolean,
+    Timestamp,
+    TimestampFmt(String),
+}
+
+impl std::str::FromStr for Conversion {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        if let Some(fmt) = s.strip_prefix("timestamp|") {
+            return Ok(Conversion::TimestampFmt(fmt.to_string()));
+        }
+        match s {
+            "bytes" => Ok(Conversion::Bytes),
+            "int" => Ok(Conversion::Integer),
+            "float" => Ok(Conversion::Float),
+            "bool" => Ok(Conversion::Boolean),
+            "timestamp" => Ok(Conversion::Timestamp),
+            other => bail!("unknown conversion kind '{}'", other),
+        }
+    }
+}
+
+impl std::fmt::Display for Conversion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Conversion::Bytes => write!(f, "bytes"),
+            Conversion::Integer => write!(f, "int"),
+            Conversion::Float => write!(f, "float"),
+            Conversion::Boolean => write!(f, "bool"),
+            Conversion::Timestamp => write!(f, "timestamp"),
+            Conversion::TimestampFmt(fmt) => write!(f, "timestamp|{}", fmt),
+        }
+    }
+}
+
+impl Serialize for Conversion {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Conversion {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl Conversion {
+    /// Coerce/validate `value` against this conversion, returning the (possibly normalized)
+    /// value or a human-readable `"expected X, found Y"` message.
+    fn coerce(&self, value: &serde_json::Value) -> std::result::Result<serde_json::Value, String> {
+        use serde_json::Value;
+        match self {
+            Conversion::Bytes => match value {
+                Value::String(_) => Ok(value.clone()),
+                other => Err(format!(
+                    "expected bytes (string), found {}",
+                    json_type_name(other)
+                )),
+            },
+            Conversion::Integer => match value {
+                Value::Number(n) if n.is_i64() || n.is_u64() => Ok(value.clone()),
+                Value::String(s) => s
+                    .parse::<i64>()
+                    .map(|i| Value::Number(i.into()))
+                    .map_err(|_| format!("expected int, found string \"{}\"", s)),
+                other => Err(format!("expected int, found {}", json_type_name(other))),
+            },
+            Conversion::Float => match value {
+                Value::Number(n) => n
+                    .as_f64()
+                    .and_then(serde_json::Number::from_f64)
+                    .map(Value::Number)
+                    .ok_or_else(|| "non-finite float".to_string()),
+                Value::String(s) => s
+                    .parse::<f64>()
+                    .ok()
+                    .and_then(serde_json::Number::from_f64)
+                    .map(Value::Number)
+                    .ok_or_else(|| format!("expected float, found string \"{}\"", s)),
+                other => Err(format!("expected float, found {}", json_type_name(other))),
+            },
+            Conversion::Boolean => match value {
+                Value::Bool(_) => Ok(value.clone()),
+                Value::String(s) if s.eq_ignore_ascii_case("true") => Ok(Value::Bool(true)),
+                Value::String(s) if s.eq_ignore_ascii_case("false") => Ok(Value::Bool(false)),
+                other => Err(format!("expected bool, found {}", json_type_name(other))),
+            },
+            Conversion::Timestamp => match value {
+                Value::String(s) => chrono::DateTime::parse_from_rfc3339(s)
+                    .map(|dt| Value::String(dt.to_rfc3339()))
+                    .map_err(|_| format!("expected RFC3339 timestamp, found \"{}\"", s)),
+                other => Err(format!(
+                    "expected timestamp (string), found {}",
+                    json_type_name(other)
+                )),
+            },
+            Conversion::TimestampFmt(fmt) => match value {
+                Value::String(s) => parse_timestamp_with_format(s, fmt)
+                    .map(|dt| Value::String(dt.format("%Y-%m-%dT%H:%M:%S%.f").to_string()))
+                    .map_err(|_| {
+                        format!(
+                            "expected timestamp matching format \"{}\", found \"{}\"",
+                            fmt, s
+                        )
+                    }),
+                other => Err(format!(
+                    "expected timestamp (string), found {}",
+                    json_type_name(other)
+                )),
+            },
+        }
+    }
+}
+
+fn json_type_name(v: &serde_json::Value) -> &'static str {
+    match v {
+        serde_json::Value::Null => "null",
+        serde_json::Value::Bool(_) => "bool",
+        serde_json::Value::Number(_) => "number",
+        serde_json::Value::String(_) => "string",
+        serde_json::Value::Array(_) => "array",
+        serde_json::Value::Object(_) => "object",
+    }
+}
+
+/// Parse `s` against `fmt` as a full timestamp, falling back to a date-only parse (time set to
+/// midnight) when `fmt` has no time fields. `NaiveDateTime::parse_from_str` alone rejects
+/// date-only formats like `"%Y-%m-%d"` with `ParseError(NotEnough)`, which would otherwise
+/// break exactly the `"timestamp|%Y-%m-%d"` example this conversion documents.
+fn parse_timestamp_with_format(
+    s: &str,
+    fmt: &str,
+) -> std::result::Result<chrono::NaiveDateTime, chrono::ParseError> {
+    chrono::NaiveDateTime::parse_from_str(s, fmt).or_else(|_| {
+        chrono::NaiveDate::parse_from_str(s, fmt).map(|d| {
+            d.and_hms_opt(0, 0, 0)
+                .expect("midnight is always a valid time")
+        })
+    })
+}
+
+/// Coerce the named fields of a single JSON object in place per `schema`. Fields absent from
+/// `obj` are left unvalidated (schemas describe optional contracts, not required fields).
+fn apply_schema_to_object(
+    node_name: &str,
+    label: &str,
+    obj: &mut serde_json::Map<String, serde_json::Value>,
+    schema: &HashMap<String, Conversion>,
+) -> Result<()> {
+    for (field, conversion) in schema {
+        if let Some(v) = obj.get(field) {
+            let coerced = conversion.coerce(v).map_err(|msg| {
+                anyhow::anyhow!(
+                    "node '{}': {} field '{}' ({}): {}",
+                    node_name,
+                    label,
+                    field,
+                    conversion,
+                    msg
+                )
+            })?;
+            obj.insert(field.clone(), coerced);
+        }
+    }
+    Ok(())
+}
+
+/// Apply `schema` to every object among the merged predecessor payloads before a node's
+/// transform runs. Non-object elements are left untouched: the schema describes named fields
+/// of whichever merged payload actually carries them.
+fn apply_input_schema(
+    node_name: &str,
+    merged: &mut [serde_json::Value],
+    schema: &HashMap<String, Conversion>,
+) -> Result<()> {
+    for value in merged.iter_mut() {
+        if let serde_json::Value::Object(obj) = value {
+            apply_schema_to_object(node_name, "input", obj, schema)?;
+        }
+    }
+    Ok(())
+}
+
+/// Validate/coerce a transform's output JSON against `schema` once it returns. Unlike inputs,
+/// the output is a single value, so it must itself be a JSON object for a field-keyed schema
+/// to apply.
+fn apply_output_schema(
+    node_name: &str,
+    output: &mut serde_json::Value,
+    schema: &HashMap<String, Conversion>,
+) -> Result<()> {
+    let obj = output.as_object_mut().with_context(|| {
+        format!(
+            "node '{}': output schema requires a JSON object, found {}",
+            node_name,
+            json_type_name(output)
+        )
+    })?;
+    apply_schema_to_object(node_name, "output", obj, schema)
+}
+
 fn ensure_dirs() -> Result<()> {
     fs::create_dir_all(Path::new(STORAGE_ROOT).join("registry").join("transforms"))?;
     fs::create_dir_all(Path::new(STORAGE_ROOT).join("states"))?;
@@ -48,12 +309,38 @@ fn ensure_dirs() -> Result<()> {
 }
 
 /// Kernel Syscalls (minimal)
-pub struct Kernel {}
+pub struct Kernel {
+    /// Caps the number of transforms run concurrently within a single wavefront.
+    /// `None` lets rayon size the pool from its global default (usually the number
+    /// of logical CPUs).
+    max_parallelism: Option<usize>,
+    /// Retry policy applied to every transform invocation. Defaults to no retries.
+    retry_policy: RetryPolicy,
+}
 
 impl Kernel {
     pub fn new() -> Result<Self> {
         ensure_dirs()?;
-        Ok(Self {})
+        Ok(Self {
+            max_parallelism: None,
+            retry_policy: RetryPolicy::default(),
+        })
+    }
+
+    /// Like [`Kernel::new`], but caps wavefront concurrency in `execute_graph` to
+    /// `max_parallelism` threads instead of using rayon's global pool size.
+    pub fn with_max_parallelism(max_parallelism: usize) -> Result<Self> {
+        ensure_dirs()?;
+        Ok(Self {
+            max_parallelism: Some(max_parallelism),
+            retry_policy: RetryPolicy::default(),
+        })
+    }
+
+    /// Builder-style setter: apply `policy` to every transform this kernel runs.
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = policy;
+        self
     }
 
     /// Register a transform in the registry (persist JSON)
@@ -90,13 +377,39 @@ impl Kernel {
 
     /// Load state by hash
     pub fn load_state(&self, hash: &str) -> Result<serde_json::Value> {
-        let path = Path::new(STORAGE_ROOT).join("states").join(format!("{}.json", hash));
+        let path = Path::new(STORAGE_ROOT)
+            .join("states")
+            .join(format!("{}.json", hash));
         let b = fs::read(&path)
             .with_context(|| format!("state {} not found at {}", hash, path.display()))?;
         let v = serde_json::from_slice(&b)?;
         Ok(v)
     }
 
+    /// Checkpoint a `Wavefield` into the content-addressed state store, returning its state
+    /// hash. This bridges the `compute::wave` subsystem with the kernel's state/trace
+    /// machinery: a running simulation can be paused, forked, or replayed deterministically,
+    /// and a checkpointed frame can be fed into `execute_graph` as an ordinary transform
+    /// input, keyed by the hash returned here.
+    pub fn persist_wavefield(&self, field: &Wavefield) -> Result<String> {
+        let payload = serde_json::json!({
+            "kind": "wavefield_snapshot",
+            "data": field.to_bytes(),
+        });
+        self.persist_state(&payload)
+    }
+
+    /// Restore a `Wavefield` previously checkpointed with `persist_wavefield`.
+    pub fn load_wavefield(&self, hash: &str) -> Result<Wavefield> {
+        let payload = self.load_state(hash)?;
+        let data = payload
+            .get("data")
+            .context("wavefield snapshot missing 'data' field")?;
+        let bytes: Vec<u8> = serde_json::from_value(data.clone())
+            .context("wavefield snapshot 'data' field is not a byte array")?;
+        Wavefield::from_bytes(&bytes).map_err(|e| anyhow::anyhow!(e))
+    }
+
     /// Emit a low-level trace (append to traces.jsonl)
     pub fn emit_trace(&self, trace: &TracePacket) -> Result<()> {
         let path = Path::new(STORAGE_ROOT).join("traces").join("traces.jsonl");
@@ -104,22 +417,28 @@ impl Kernel {
             .create(true)
             .append(true)
             .open(path)?;
-        let json = serde_json::to_string(trace)?;
-        writeln!(f, "{}", json)?;
+        // Build the whole line (body + newline) up front and issue a single `write_all`, so
+        // that concurrent wavefront workers appending to the same file (each via its own
+        // O_APPEND `File`) can't interleave a body and newline from two different traces.
+        let mut line = serde_json::to_string(trace)?;
+        line.push('\n');
+        f.write_all(line.as_bytes())?;
         Ok(())
     }
 
-    /// Naive execute_graph: takes a DAG defined by Node IDs and edges, executes transforms
-    /// Assumption: each node has a single transform id and consumes outputs of predecessors as input merged as JSON.
-    pub fn execute_graph(
-        &self,
-        graph_spec: GraphSpec,
-        input_state_hash: &str,
-    ) -> Result<String> {
-        // load input state
-        let mut state_inputs: HashMap<String, String> = HashMap::new();
+    /// Execute a DAG defined by Node IDs and edges, running transforms wavefront-by-wavefront.
+    ///
+    /// Assumption: each node has a single transform id and consumes outputs of predecessors as
+    /// input merged as JSON. Unlike a plain topological walk, nodes with no dependency on one
+    /// another are batched into the same "wavefront" and executed concurrently with rayon; only
+    /// the shared `outputs`/`state_inputs` maps need synchronization, since `persist_state` is
+    /// content-addressed and `run_transform_with_io` writes its temp files into its own
+    /// `Uuid`-named directory, so concurrent writes never collide.
+    pub fn execute_graph(&self, graph_spec: GraphSpec, input_state_hash: &str) -> Result<String> {
+        // map "__input" (and, in principle, other shared inputs) -> state hash
+        let state_inputs: Mutex<HashMap<String, String>> = Mutex::new(HashMap::new());
         // map node name -> output state hash
-        let mut outputs: HashMap<String, String> = HashMap::new();
+        let outputs: Mutex<HashMap<String, String>> = Mutex::new(HashMap::new());
 
         // Build petgraph graph
         let mut graph = DiGraph::<String, ()>::new();
@@ -129,64 +448,138 @@ impl Kernel {
             node_map.insert(node.name.clone(), idx);
         }
         for edge in &graph_spec.edges {
-            let a = node_map
-                .get(&edge.from)
-                .context("edge from unknown node")?;
+            let a = node_map.get(&edge.from).context("edge from unknown node")?;
             let b = node_map.get(&edge.to).context("edge to unknown node")?;
             graph.add_edge(*a, *b, ());
         }
 
-        // topological order
-        let mut topo = Topo::new(&graph);
         let exec_id = Uuid::new_v4().to_string();
 
         // store the original input under a pseudo node "__input"
         let root_input_hash = input_state_hash.to_string();
-        state_inputs.insert("__input".into(), root_input_hash);
-
-        while let Some(nx) = topo.next(&graph) {
-            let node_name = &graph[nx];
-            // gather predecessors outputs
-            let preds: Vec<_> = graph
-                .neighbors_directed(nx, petgraph::Direction::Incoming)
-                .map(|nidx| graph[nidx].clone())
-                .collect();
-
-            // merge predecessor outputs into one JSON (simple array or single item)
-            let mut merged = Vec::new();
-            if preds.is_empty() {
-                // use root input
-                let root = self.load_state(&root_input_hash)?;
-                merged.push(root);
-            } else {
-                for p in preds {
-                    if let Some(h) = outputs.get(&p) {
-                        let st = self.load_state(h)?;
-                        merged.push(st);
-                    } else {
-                        bail!("Missing output from predecessor {}", p);
+        state_inputs
+            .lock()
+            .unwrap()
+            .insert("__input".into(), root_input_hash.clone());
+
+        // in-degree per node, used to discover each wavefront of ready nodes
+        let mut in_degree: HashMap<NodeIndex, usize> = graph
+            .node_indices()
+            .map(|nx| {
+                let d = graph
+                    .neighbors_directed(nx, petgraph::Direction::Incoming)
+                    .count();
+                (nx, d)
+            })
+            .collect();
+        let mut ready: Vec<NodeIndex> = in_degree
+            .iter()
+            .filter(|(_, &d)| d == 0)
+            .map(|(&nx, _)| nx)
+            .collect();
+
+        let pool = match self.max_parallelism {
+            Some(n) => Some(
+                rayon::ThreadPoolBuilder::new()
+                    .num_threads(n)
+                    .build()
+                    .context("failed to build wavefront thread pool")?,
+            ),
+            None => None,
+        };
+
+        let mut executed = 0usize;
+        while !ready.is_empty() {
+            let wavefront = std::mem::take(&mut ready);
+
+            let run_wavefront = || -> Result<Vec<(NodeIndex, String, String)>> {
+                wavefront
+                    .par_iter()
+                    .map(|&nx| -> Result<(NodeIndex, String, String)> {
+                        let node_name = graph[nx].clone();
+                        let preds: Vec<String> = graph
+                            .neighbors_directed(nx, petgraph::Direction::Incoming)
+                            .map(|nidx| graph[nidx].clone())
+                            .collect();
+
+                        // run transform for this node
+                        let node_spec = graph_spec
+                            .nodes
+                            .iter()
+                            .find(|n| n.name == node_name)
+                            .context("node spec missing")?;
+
+                        // merge predecessor outputs into one JSON (simple array or single item)
+                        let mut merged = Vec::new();
+                        if preds.is_empty() {
+                            merged.push(self.load_state(&root_input_hash)?);
+                        } else {
+                            for p in &preds {
+                                let h = outputs.lock().unwrap().get(p).cloned();
+                                let h = h.with_context(|| {
+                                    format!("Missing output from predecessor {}", p)
+                                })?;
+                                merged.push(self.load_state(&h)?);
+                            }
+                        }
+                        if let Some(schema) = &node_spec.input_schema {
+                            apply_input_schema(&node_name, &mut merged, schema)?;
+                        }
+                        let merged_json = serde_json::Value::Array(merged);
+                        let input_hash = self.persist_state(&merged_json)?;
+
+                        let transform = self.load_transform(&node_spec.transform_id)?;
+                        let (output_hash, trace) = self.run_transform_with_io(
+                            &transform,
+                            &input_hash,
+                            &exec_id,
+                            &node_name,
+                            node_spec.output_schema.as_ref(),
+                        )?;
+
+                        // write trace
+                        self.emit_trace(&trace)?;
+                        Ok((nx, node_name, output_hash))
+                    })
+                    .collect()
+            };
+
+            let results = match &pool {
+                Some(p) => p.install(run_wavefront),
+                None => run_wavefront(),
+            }?;
+
+            {
+                let mut outputs_guard = outputs.lock().unwrap();
+                for (_, node_name, output_hash) in &results {
+                    outputs_guard.insert(node_name.clone(), output_hash.clone());
+                }
+            }
+
+            for (nx, _, _) in &results {
+                executed += 1;
+                for succ in graph.neighbors_directed(*nx, petgraph::Direction::Outgoing) {
+                    let d = in_degree
+                        .get_mut(&succ)
+                        .expect("successor missing in-degree entry");
+                    *d -= 1;
+                    if *d == 0 {
+                        ready.push(succ);
                     }
                 }
             }
-            let merged_json = serde_json::Value::Array(merged);
-            let input_hash = self.persist_state(&merged_json)?;
-
-            // run transform for this node
-            let node_spec = graph_spec
-                .nodes
-                .iter()
-                .find(|n| &n.name == node_name)
-                .context("node spec missing")?;
-            let transform = self.load_transform(&node_spec.transform_id)?;
-            let (output_hash, trace) = self.run_transform_with_io(&transform, &input_hash, &exec_id)?;
-
-            // write trace
-            self.emit_trace(&trace)?;
-            outputs.insert(node_name.clone(), output_hash);
+        }
+        if executed != graph.node_count() {
+            bail!(
+                "cycle detected in graph: {} of {} nodes executed",
+                executed,
+                graph.node_count()
+            );
         }
 
-        // final outputs: gather outputs of nodes marked as sinks
-        let mut final_outputs = HashMap::new();
+        // final outputs: gather outputs of nodes marked as sinks, sorted for determinism
+        let outputs = outputs.into_inner().unwrap();
+        let mut final_outputs: BTreeMap<String, String> = BTreeMap::new();
         for sink in &graph_spec.sinks {
             if let Some(h) = outputs.get(sink) {
                 final_outputs.insert(sink.clone(), h.clone());
@@ -214,8 +607,10 @@ impl Kernel {
         Ok(spec)
     }
 
-    /// Run the transform's exec_command by writing input to a temp file and calling:
-    /// <exec_command> <input_path> <output_path>
+    /// Run the transform (native process or in-process WASM module) by writing its input to
+    /// a temp file, invoking it with `<input_path> <output_path>`, and hashing the result.
+    /// If `output_schema` is given, the output JSON is coerced/validated against it before
+    /// being persisted.
     ///
     /// Returns (output_state_hash, TracePacket)
     fn run_transform_with_io(
@@ -223,42 +618,62 @@ impl Kernel {
         transform: &TransformSpec,
         input_hash: &str,
         execution_id: &str,
+        node_name: &str,
+        output_schema: Option<&HashMap<String, Conversion>>,
     ) -> Result<(String, TracePacket)> {
         let input_val = self.load_state(input_hash)?;
-        // write input temp file
-        let input_file = Path::new(STORAGE_ROOT)
+        // Give this invocation its own temp subdirectory rather than dropping its input/output
+        // files into the shared `tmp/` alongside every other in-flight invocation: the WASM
+        // path preopens this directory for the guest module, so a shared directory would let
+        // one transform read or clobber another's temp files through that mount.
+        let invocation_dir = Path::new(STORAGE_ROOT)
             .join("tmp")
-            .join(format!("input-{}.json", Uuid::new_v4()));
-        fs::create_dir_all(input_file.parent().unwrap())?;
+            .join(Uuid::new_v4().to_string());
+        fs::create_dir_all(&invocation_dir)?;
+
+        let input_file = invocation_dir.join("input.json");
         fs::write(&input_file, serde_json::to_vec(&input_val)?)?;
 
-        let output_file = Path::new(STORAGE_ROOT)
-            .join("tmp")
-            .join(format!("output-{}.json", Uuid::new_v4()));
+        let output_file = invocation_dir.join("output.json");
 
-        // split exec_command into program + args (naive)
-        let parts: Vec<&str> = transform.exec_command.split_whitespace().collect();
-        if parts.is_empty() {
-            bail!("empty exec_command for transform {}", transform.id);
-        }
-        let prog = parts[0];
-        let args: Vec<&str> = parts[1..].to_vec();
+        let total_t0 = std::time::Instant::now();
+        let max_attempts = self.retry_policy.max_attempts.max(1);
+        let mut attempts = Vec::with_capacity(max_attempts as usize);
+        let mut success = false;
+        let mut error = None;
 
-        // Build command with input and output file args appended
-        let mut cmd = Command::new(prog);
-        for a in args {
-            cmd.arg(a);
+        for attempt in 0..max_attempts {
+            let t0 = std::time::Instant::now();
+            let (attempt_success, resource_usage, attempt_error) = match &transform.kind {
+                TransformKind::Native { exec_command } => {
+                    self.run_native_transform(exec_command, &input_file, &output_file)?
+                }
+                TransformKind::Wasm { module_path } => {
+                    self.run_wasm_transform(module_path, &input_file, &output_file)?
+                }
+            };
+            attempts.push(serde_json::json!({
+                "attempt": attempt + 1,
+                "duration_ms": t0.elapsed().as_millis(),
+                "resource_usage": resource_usage,
+                "error": attempt_error,
+            }));
+            success = attempt_success;
+            error = attempt_error;
+            if success || attempt + 1 == max_attempts {
+                break;
+            }
+            let backoff_ms = (self.retry_policy.initial_backoff_ms as f64
+                * self.retry_policy.multiplier.powi(attempt as i32))
+                as u64;
+            if backoff_ms > 0 {
+                std::thread::sleep(std::time::Duration::from_millis(backoff_ms));
+            }
         }
-        cmd.arg(input_file.as_os_str());
-        cmd.arg(output_file.as_os_str());
+        let duration = total_t0.elapsed().as_millis();
+        let attempt_count = attempts.len() as u32;
 
-        let t0 = std::time::Instant::now();
-        let res = cmd.output()?;
-        let duration = t0.elapsed().as_millis();
-
-        if !res.status.success() {
-            // capture stderr for trace error
-            let err = String::from_utf8_lossy(&res.stderr).to_string();
+        if !success {
             let trace = TracePacket {
                 trace_id: Uuid::new_v4().to_string(),
                 execution_id: execution_id.to_string(),
@@ -267,16 +682,24 @@ impl Kernel {
                 inputs_hash: input_hash.to_string(),
                 outputs_hash: "".to_string(),
                 duration_ms: duration,
-                resource_usage: serde_json::json!({ "exit_code": res.status.code() }),
-                error: Some(err),
+                attempt_count,
+                resource_usage: serde_json::json!({ "attempts": attempts }),
+                error,
             };
             return Ok(("".to_string(), trace));
         }
 
         // read output file
-        let output_bytes = fs::read(&output_file)
-            .with_context(|| format!("expected transform to write output at {}", output_file.display()))?;
-        let output_val: serde_json::Value = serde_json::from_slice(&output_bytes)?;
+        let output_bytes = fs::read(&output_file).with_context(|| {
+            format!(
+                "expected transform to write output at {}",
+                output_file.display()
+            )
+        })?;
+        let mut output_val: serde_json::Value = serde_json::from_slice(&output_bytes)?;
+        if let Some(schema) = output_schema {
+            apply_output_schema(node_name, &mut output_val, schema)?;
+        }
         let output_hash = self.persist_state(&output_val)?;
 
         let trace = TracePacket {
@@ -287,16 +710,125 @@ impl Kernel {
             inputs_hash: input_hash.to_string(),
             outputs_hash: output_hash.clone(),
             duration_ms: duration,
-            resource_usage: serde_json::json!({ "output_bytes": output_bytes.len() }),
+            attempt_count,
+            resource_usage: serde_json::json!({
+                "attempts": attempts,
+                "output_bytes": output_bytes.len(),
+            }),
             error: None,
         };
 
         // cleanup temp files (optional)
-        let _ = fs::remove_file(&input_file);
-        let _ = fs::remove_file(&output_file);
+        let _ = fs::remove_dir_all(&invocation_dir);
 
         Ok((output_hash, trace))
     }
+
+    /// Run a transform by spawning `exec_command` as `<exec_command> <input_path>
+    /// <output_path>`. Returns (success, resource_usage, error_message); a non-zero exit is
+    /// reported via the returned flag/message rather than `Err`, mirroring the WASM path so
+    /// callers handle both uniformly.
+    fn run_native_transform(
+        &self,
+        exec_command: &str,
+        input_file: &Path,
+        output_file: &Path,
+    ) -> Result<(bool, serde_json::Value, Option<String>)> {
+        // split exec_command into program + args (naive)
+        let parts: Vec<&str> = exec_command.split_whitespace().collect();
+        if parts.is_empty() {
+            bail!("empty exec_command");
+        }
+        let prog = parts[0];
+        let args: Vec<&str> = parts[1..].to_vec();
+
+        // Build command with input and output file args appended
+        let mut cmd = Command::new(prog);
+        for a in args {
+            cmd.arg(a);
+        }
+        cmd.arg(input_file.as_os_str());
+        cmd.arg(output_file.as_os_str());
+
+        let res = cmd.output()?;
+        let resource_usage = serde_json::json!({ "exit_code": res.status.code() });
+        if res.status.success() {
+            Ok((true, resource_usage, None))
+        } else {
+            let err = String::from_utf8_lossy(&res.stderr).to_string();
+            Ok((false, resource_usage, Some(err)))
+        }
+    }
+
+    /// Run a compiled WASI module in-process via wasmtime, sandboxed and fuel-metered.
+    /// The input/output temp files are mounted through a WASI preopen so the module sees the
+    /// same `<input_path> <output_path>` argv contract as a native transform.
+    fn run_wasm_transform(
+        &self,
+        module_path: &str,
+        input_file: &Path,
+        output_file: &Path,
+    ) -> Result<(bool, serde_json::Value, Option<String>)> {
+        const FUEL_BUDGET: u64 = 10_000_000_000;
+
+        let mut config = wasmtime::Config::new();
+        config.consume_fuel(true);
+        let engine = wasmtime::Engine::new(&config)?;
+        let module = wasmtime::Module::from_file(&engine, module_path)
+            .with_context(|| format!("failed to load wasm module {}", module_path))?;
+
+        let io_dir = input_file
+            .parent()
+            .context("input file has no parent directory")?;
+        let input_name = input_file
+            .file_name()
+            .context("input file has no name")?
+            .to_string_lossy()
+            .into_owned();
+        let output_name = output_file
+            .file_name()
+            .context("output file has no name")?
+            .to_string_lossy()
+            .into_owned();
+
+        let preopen_dir = wasmtime_wasi::sync::Dir::open_ambient_dir(
+            io_dir,
+            wasmtime_wasi::sync::ambient_authority(),
+        )?;
+        let wasi = wasmtime_wasi::sync::WasiCtxBuilder::new()
+            .inherit_stdio()
+            .preopened_dir(preopen_dir, "/io")?
+            .arg("transform")?
+            .arg(format!("/io/{}", input_name))?
+            .arg(format!("/io/{}", output_name))?
+            .build();
+
+        let mut linker = wasmtime::Linker::new(&engine);
+        wasmtime_wasi::sync::add_to_linker(&mut linker, |ctx| ctx)?;
+
+        let mut store = wasmtime::Store::new(&engine, wasi);
+        store.set_fuel(FUEL_BUDGET)?;
+
+        let instance = linker.instantiate(&mut store, &module)?;
+        let start = instance.get_typed_func::<(), ()>(&mut store, "_start")?;
+        let fuel_before = store.get_fuel()?;
+        let run_result = start.call(&mut store, ());
+        let fuel_used = fuel_before.saturating_sub(store.get_fuel().unwrap_or(0));
+        let memory_bytes = instance
+            .get_memory(&mut store, "memory")
+            .map(|m| m.data_size(&store))
+            .unwrap_or(0);
+
+        let resource_usage = serde_json::json!({
+            "fuel_used": fuel_used,
+            "memory_bytes": memory_bytes,
+        });
+
+        match run_result {
+            Ok(()) => Ok((true, resource_usage, None)),
+            Err(e) => Ok((false, resource_usage, Some(e.to_string()))),
+        }
+    }
 }
 
 /// ---- Simple graph spec types ----
@@ -304,6 +836,15 @@ impl Kernel {
 pub struct GraphNode {
     pub name: String,
     pub transform_id: String,
+    /// Declarative type contract applied to named top-level fields of each JSON object among
+    /// the merged predecessor payloads before the transform runs. Coercion/validation failures abort the graph before the
+    /// transform is invoked.
+    #[serde(default)]
+    pub input_schema: Option<HashMap<String, Conversion>>,
+    /// Declarative type contract validated (and coerced) against the transform's output
+    /// once it returns.
+    #[serde(default)]
+    pub output_schema: Option<HashMap<String, Conversion>>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -329,7 +870,9 @@ fn main() -> Result<()> {
     // Note: on Windows, replace "cat" with "type" or create a small script.
     let transform_spec = TransformSpec {
         id: "identity".into(),
-        exec_command: "sh -c 'cat'".into(), // this runs `sh -c cat <in> <out>` -- works on unix shells
+        kind: TransformKind::Native {
+            exec_command: "sh -c 'cat'".into(), // this runs `sh -c cat <in> <out>` -- works on unix shells
+        },
         meta: serde_json::json!({"desc":"identity demo (cat)"}),
     };
     let transform_id = kernel.create_transform(&transform_spec)?;
@@ -344,6 +887,8 @@ fn main() -> Result<()> {
     let node = GraphNode {
         name: "node1".into(),
         transform_id: transform_id.clone(),
+        input_schema: None,
+        output_schema: None,
     };
     let graph = GraphSpec {
         nodes: vec![node],
@@ -356,7 +901,285 @@ fn main() -> Result<()> {
 
     // Print summary for convenience
     let summary = kernel.load_state(&exec_summary_hash)?;
-    println!("Execution summary: {}", serde_json::to_string_pretty(&summary)?);
+    println!(
+        "Execution summary: {}",
+        serde_json::to_string_pretty(&summary)?
+    );
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn identity_node(name: &str, kernel: &Kernel) -> Result<GraphNode> {
+        let transform_id = kernel.create_transform(&TransformSpec {
+            id: format!("identity_{}", name),
+            kind: TransformKind::Native {
+                exec_command: "cp".into(),
+            },
+            meta: serde_json::json!({"desc": "identity (cp) for tests"}),
+        })?;
+        Ok(GraphNode {
+            name: name.into(),
+            transform_id,
+            input_schema: None,
+            output_schema: None,
+        })
+    }
+
+    /// `execute_graph` batches nodes with no dependency on one another into the same
+    /// wavefront and runs them concurrently via rayon; this exercises that on a diamond DAG
+    /// (A -> B, A -> C, B -> D, C -> D, so B and C share a wavefront) and checks both that
+    /// every node's output actually gets collected (in-degree bookkeeping is correct) and that
+    /// `final_outputs` is sorted by node name regardless of the order `sinks` lists them in.
+    #[test]
+    fn execute_graph_runs_a_diamond_wavefront_and_sorts_final_outputs() {
+        let kernel = Kernel::new().expect("kernel init");
+
+        let graph = GraphSpec {
+            nodes: vec![
+                identity_node("A", &kernel).unwrap(),
+                identity_node("B", &kernel).unwrap(),
+                identity_node("C", &kernel).unwrap(),
+                identity_node("D", &kernel).unwrap(),
+            ],
+            edges: vec![
+                GraphEdge {
+                    from: "A".into(),
+                    to: "B".into(),
+                },
+                GraphEdge {
+                    from: "A".into(),
+                    to: "C".into(),
+                },
+                GraphEdge {
+                    from: "B".into(),
+                    to: "D".into(),
+                },
+                GraphEdge {
+                    from: "C".into(),
+                    to: "D".into(),
+                },
+            ],
+            // Listed out of alphabetical order to exercise the sorted-keys guarantee.
+            sinks: vec!["D".into(), "A".into()],
+        };
+
+        let input_hash = kernel
+            .persist_state(&serde_json::json!({"seed": 1}))
+            .expect("persist root input");
+
+        let summary_hash = kernel
+            .execute_graph(graph, &input_hash)
+            .expect("execute_graph should run the whole diamond");
+        let summary = kernel.load_state(&summary_hash).expect("load summary");
+
+        let final_outputs = summary
+            .get("final_outputs")
+            .expect("final_outputs present")
+            .as_object()
+            .expect("final_outputs is an object");
+        assert_eq!(final_outputs.len(), 2);
+
+        // BTreeMap-backed final_outputs must serialize "A" before "D" no matter what order
+        // `sinks` listed them in.
+        let rendered = serde_json::to_string(final_outputs).unwrap();
+        assert!(rendered.find("\"A\"").unwrap() < rendered.find("\"D\"").unwrap());
+
+        let d_hash = final_outputs
+            .get("D")
+            .and_then(|v| v.as_str())
+            .expect("D has an output hash");
+        let d_output = kernel.load_state(d_hash).expect("load D's output");
+        // D has two predecessors (B and C); if the wavefront scheduler dropped one (e.g. a
+        // bad in-degree decrement), D would have run with only one of them merged in.
+        assert_eq!(d_output.as_array().map(|a| a.len()), Some(2));
+    }
+
+    #[test]
+    fn conversion_coerces_int_from_native_number_and_string() {
+        assert_eq!(
+            Conversion::Integer.coerce(&serde_json::json!(42)).unwrap(),
+            serde_json::json!(42)
+        );
+        assert_eq!(
+            Conversion::Integer
+                .coerce(&serde_json::json!("42"))
+                .unwrap(),
+            serde_json::json!(42)
+        );
+        assert!(Conversion::Integer
+            .coerce(&serde_json::json!("not a number"))
+            .is_err());
+    }
+
+    #[test]
+    fn conversion_coerces_float_from_native_number_and_string() {
+        assert_eq!(
+            Conversion::Float.coerce(&serde_json::json!(1.5)).unwrap(),
+            serde_json::json!(1.5)
+        );
+        assert_eq!(
+            Conversion::Float.coerce(&serde_json::json!("1.5")).unwrap(),
+            serde_json::json!(1.5)
+        );
+        assert!(Conversion::Float
+            .coerce(&serde_json::json!("not a float"))
+            .is_err());
+    }
+
+    #[test]
+    fn conversion_coerces_bool_from_native_bool_and_string() {
+        assert_eq!(
+            Conversion::Boolean
+                .coerce(&serde_json::json!(true))
+                .unwrap(),
+            serde_json::json!(true)
+        );
+        assert_eq!(
+            Conversion::Boolean
+                .coerce(&serde_json::json!("TRUE"))
+                .unwrap(),
+            serde_json::json!(true)
+        );
+        assert_eq!(
+            Conversion::Boolean
+                .coerce(&serde_json::json!("false"))
+                .unwrap(),
+            serde_json::json!(false)
+        );
+        assert!(Conversion::Boolean
+            .coerce(&serde_json::json!("nope"))
+            .is_err());
+    }
+
+    #[test]
+    fn conversion_coerces_rfc3339_timestamp() {
+        let coerced = Conversion::Timestamp
+            .coerce(&serde_json::json!("2024-01-02T03:04:05Z"))
+            .unwrap();
+        assert_eq!(coerced, serde_json::json!("2024-01-02T03:04:05+00:00"));
+
+        assert!(Conversion::Timestamp
+            .coerce(&serde_json::json!("not a timestamp"))
+            .is_err());
+    }
+
+    #[test]
+    fn conversion_timestamp_fmt_falls_back_to_date_only_parse() {
+        // Regression test for the date-only `"timestamp|%Y-%m-%d"` example documented on
+        // `Conversion`: `NaiveDateTime::parse_from_str` alone rejects it with `NotEnough`.
+        let conversion = Conversion::TimestampFmt("%Y-%m-%d".into());
+        let coerced = conversion.coerce(&serde_json::json!("2024-01-02")).unwrap();
+        assert_eq!(coerced, serde_json::json!("2024-01-02T00:00:00"));
+
+        assert!(conversion.coerce(&serde_json::json!("not-a-date")).is_err());
+    }
+
+    #[test]
+    fn apply_schema_to_object_reports_node_and_field_on_type_mismatch() {
+        let mut obj = serde_json::Map::new();
+        obj.insert("count".into(), serde_json::json!("not an int"));
+        let mut schema = HashMap::new();
+        schema.insert("count".into(), Conversion::Integer);
+
+        let err = apply_schema_to_object("my_node", "input", &mut obj, &schema).unwrap_err();
+        let msg = err.to_string();
+        assert!(msg.contains("my_node"));
+        assert!(msg.contains("input"));
+        assert!(msg.contains("count"));
+    }
+
+    /// `false` always exits non-zero, so every attempt fails: the retry loop must give up only
+    /// after `max_attempts`, and report exactly that many tries in both `attempt_count` and the
+    /// per-attempt `resource_usage.attempts` log.
+    #[test]
+    fn run_transform_with_io_gives_up_after_max_attempts() {
+        let kernel = Kernel::new()
+            .expect("kernel init")
+            .with_retry_policy(RetryPolicy {
+                max_attempts: 3,
+                initial_backoff_ms: 0,
+                multiplier: 1.0,
+            });
+
+        let transform = TransformSpec {
+            id: "always_fails".into(),
+            kind: TransformKind::Native {
+                exec_command: "false".into(),
+            },
+            meta: serde_json::json!({}),
+        };
+        let input_hash = kernel
+            .persist_state(&serde_json::json!({"k": "v"}))
+            .expect("persist input");
+
+        let (output_hash, trace) = kernel
+            .run_transform_with_io(&transform, &input_hash, "test-exec", "node1", None)
+            .expect("run_transform_with_io should return Ok even when the transform fails");
+
+        assert_eq!(output_hash, "");
+        assert_eq!(trace.attempt_count, 3);
+        assert!(trace.error.is_some());
+        let attempts = trace
+            .resource_usage
+            .get("attempts")
+            .and_then(|v| v.as_array())
+            .expect("resource_usage.attempts is an array");
+        assert_eq!(attempts.len(), 3);
+    }
+
+    /// A transform that fails on its first attempt and succeeds on the second must report
+    /// `attempt_count == 2` and still produce a usable output, proving the loop both retries on
+    /// failure and stops retrying once a later attempt succeeds.
+    #[test]
+    fn run_transform_with_io_succeeds_after_a_retry() {
+        let kernel = Kernel::new()
+            .expect("kernel init")
+            .with_retry_policy(RetryPolicy {
+                max_attempts: 2,
+                initial_backoff_ms: 0,
+                multiplier: 1.0,
+            });
+
+        // `run_native_transform` splits `exec_command` on whitespace with no shell-style
+        // quoting, so the flaky logic has to live in a script file invoked as `sh <path>`
+        // (two whitespace-free tokens) rather than inline via `sh -c "..."`.
+        let scratch_dir = std::env::temp_dir().join(format!("retry_test_{}", Uuid::new_v4()));
+        fs::create_dir_all(&scratch_dir).expect("create scratch dir");
+        let counter_file = scratch_dir.join("attempts");
+        let script_file = scratch_dir.join("flaky.sh");
+        fs::write(
+            &script_file,
+            format!(
+                "n=$(cat {counter} 2>/dev/null || echo 0); n=$((n+1)); echo $n > {counter}\n\
+                 if [ \"$n\" -lt 2 ]; then exit 1; else cp \"$1\" \"$2\"; fi\n",
+                counter = counter_file.display()
+            ),
+        )
+        .expect("write flaky script");
+
+        let transform = TransformSpec {
+            id: "flaky".into(),
+            kind: TransformKind::Native {
+                exec_command: format!("sh {}", script_file.display()),
+            },
+            meta: serde_json::json!({}),
+        };
+        let input_hash = kernel
+            .persist_state(&serde_json::json!({"k": "v"}))
+            .expect("persist input");
+
+        let (output_hash, trace) = kernel
+            .run_transform_with_io(&transform, &input_hash, "test-exec", "node1", None)
+            .expect("run_transform_with_io should succeed after the retry");
+
+        assert_ne!(output_hash, "");
+        assert_eq!(trace.attempt_count, 2);
+        assert!(trace.error.is_none());
+
+        let _ = fs::remove_dir_all(&scratch_dir);
+    }
+}
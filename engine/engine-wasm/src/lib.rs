@@ -1,6 +1,6 @@
-use wasm_bindgen::prelude::*;
 use engine_core::compute;
 use engine_core::compute::wave::Wavefield;
+use wasm_bindgen::prelude::*;
 
 #[wasm_bindgen(start)]
 pub fn start() {
@@ -94,10 +94,33 @@ impl WasmWavefield {
         self.inner.seed_point(x, y, value);
     }
 
+    /// Install a per-cell wave-speed field (length must be `width * height`), enabling
+    /// heterogeneous/layered media instead of a single scalar `c` passed to `step`. Returns a
+    /// `JsValue` error (instead of trapping) if `velocity.length` doesn't match.
+    #[wasm_bindgen]
+    pub fn set_velocity_field(&mut self, velocity: Vec<f32>) -> Result<(), JsValue> {
+        if velocity.len() != self.inner.len() {
+            return Err(JsValue::from_str(&format!(
+                "velocity field length {} does not match width*height {}",
+                velocity.len(),
+                self.inner.len()
+            )));
+        }
+        self.inner.set_velocity_field(velocity);
+        Ok(())
+    }
+
+    /// Install a damping "sponge" absorbing boundary `width` cells deep, ramping from 0 in
+    /// the interior up to `d_max` at the edge, so outgoing waves dissipate instead of
+    /// reflecting back into the domain.
+    #[wasm_bindgen]
+    pub fn set_absorbing_border(&mut self, width: usize, d_max: f32) {
+        self.inner.set_absorbing_border(width, d_max);
+    }
+
     /// Zero all three buffers
     #[wasm_bindgen]
     pub fn reset(&mut self) {
         self.inner.reset();
     }
 }
-